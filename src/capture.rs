@@ -0,0 +1,129 @@
+/*
+ * Copyright 2015 Jonathan Anderson
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Live capture (and saved-pcap-file) packet sources, feeding frames
+//! straight through the dissector pipeline.
+//!
+//! A `Capture` is an `Iterator` of dissected frames, so a consumer can
+//! write:
+//!
+//! ```no_run
+//! for (ts, len, val) in rshark::capture::Capture::from_pcap_file("dump.pcap").unwrap() {
+//!     match val {
+//!         Ok(val) => print!["{}", val.pretty_print(0, false)],
+//!         Err(e) => println!["Error: {}", e],
+//!     }
+//! }
+//! ```
+
+use std::path::Path;
+
+use ethernet;
+use Error;
+
+/// `pcap`'s own fallible operations report `pcap::Error`, not our own
+/// `Error`, so they get their own result alias rather than overloading
+/// `::Result`.
+pub type PcapResult<T> = ::std::result::Result<T, pcap::Error>;
+
+/// The two kinds of packet source that `libpcap` can give us: a live
+/// interface or a previously-saved capture file. We need to keep both
+/// around as a single type because `pcap::Capture<T>`'s state is encoded
+/// in its type parameter.
+enum Source {
+    Live(pcap::Capture<pcap::Active>),
+    File(pcap::Capture<pcap::Offline>),
+}
+
+/// A source of packets, dissected as they are captured.
+pub struct Capture {
+    source: Source,
+    linktype: pcap::Linktype,
+}
+
+/// A single captured frame: the time it was captured, its original length
+/// on the wire (which may be greater than what was actually captured, if
+/// the capture used a snap length), and the result of dissecting it.
+pub type Frame = (Timestamp, u32, ::Result);
+
+/// A capture timestamp, as reported by `libpcap` (seconds and
+/// microseconds since the Unix epoch).
+#[derive(Copy, Clone, Debug)]
+pub struct Timestamp {
+    pub seconds: i64,
+    pub microseconds: i64,
+}
+
+impl Capture {
+    /// Open a live network interface by name (e.g., `"eth0"`) and start
+    /// capturing from it.
+    pub fn from_interface(name: &str) -> PcapResult<Capture> {
+        let cap = try![try![pcap::Capture::from_device(name)].open()];
+        let linktype = cap.get_datalink();
+
+        Ok(Capture { source: Source::Live(cap), linktype: linktype })
+    }
+
+    /// Open a previously-saved capture file (e.g., written by `tcpdump -w`).
+    pub fn from_pcap_file<P: AsRef<Path>>(path: P) -> PcapResult<Capture> {
+        let cap = try![pcap::Capture::from_file(path)];
+        let linktype = cap.get_datalink();
+
+        Ok(Capture { source: Source::File(cap), linktype: linktype })
+    }
+
+}
+
+/// Dissect one captured frame according to its link-layer type.
+///
+/// Note: `ethernet` is declared as a module in `lib.rs` but `src/ethernet.rs`
+/// doesn't exist in this tree (nor does `src/ip.rs`, nor a `Cargo.toml` to
+/// build either this or `pcap`/`base64`), so this call path doesn't
+/// currently compile or run. Left wired as-is rather than stubbed out, for
+/// whoever adds those modules.
+fn dissect(linktype: pcap::Linktype, data: &[u8]) -> ::Result {
+    match linktype {
+        pcap::Linktype::ETHERNET => ethernet::dissect(data, 0),
+        other => Error::inval(format!["unsupported link-layer type {:?}", other]),
+    }
+}
+
+impl Iterator for Capture {
+    type Item = Frame;
+
+    /// Pull the next frame off the wire (or out of the file) and run it
+    /// through the appropriate top-level dissector. Returns `None` once
+    /// a live interface hits a fatal error or a capture file is exhausted.
+    fn next(&mut self) -> Option<Frame> {
+        let linktype = self.linktype;
+
+        loop {
+            let packet = match self.source {
+                Source::Live(ref mut cap) => cap.next_packet(),
+                Source::File(ref mut cap) => cap.next_packet(),
+            };
+
+            match packet {
+                Ok(packet) => {
+                    let ts = Timestamp {
+                        seconds: packet.header.ts.tv_sec as i64,
+                        microseconds: packet.header.ts.tv_usec as i64,
+                    };
+                    let val = dissect(linktype, packet.data);
+
+                    return Some((ts, packet.header.len, val));
+                }
+                // A live interface with nothing to read times out between
+                // packets; that's not end-of-capture, just "try again".
+                Err(pcap::Error::TimeoutExpired) => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+}
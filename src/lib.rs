@@ -41,16 +41,18 @@
 //! The `rshark` library provides packet dissection functions such as
 //! `rshark::ethernet::dissect()`. Every such dissection function, which should
 //! conform to the `rshark::Dissector` function type, takes as input a slice of bytes
-//! and returns an `rshark::Result` (which defaults to
-//! `Result<rshark::Val, rshark::Error>`).
+//! and the absolute offset of that slice within the original frame (so that
+//! nested dissectors can report byte ranges relative to the whole frame
+//! rather than their own local slice), and returns an `rshark::Result`
+//! (which defaults to `Result<rshark::Val, rshark::Error>`).
 //! Usage is pretty simple:
 //!
 //! ```
 //! let data = vec![];
 //!
-//! match rshark::ethernet::dissect(&data) {
+//! match rshark::ethernet::dissect(&data, 0) {
 //!     Err(e) => println!["Error: {}", e],
-//!     Ok(val) => print!["{}", val.pretty_print(0)],
+//!     Ok(val) => print!["{}", val.pretty_print(0, false)],
 //! }
 //! ```
 //!
@@ -60,13 +62,17 @@
 
 #![doc(html_logo_url = "https://raw.githubusercontent.com/musec/rusty-shark/master/artwork/wordmark.png")]
 
+extern crate base64;
 extern crate byteorder;
 extern crate num;
+extern crate pcap;
 extern crate promising_future;
 
 use byteorder::ByteOrder;
 pub use promising_future::Future;
+use std::borrow::Cow;
 use std::fmt;
+use std::ops::Range;
 
 
 /// A description of a protocol, including code that can parse it.
@@ -78,18 +84,16 @@ pub trait Protocol {
     fn full_name(&self) -> &'static str;
 
     /// A function to dissect some bytes according to the protocol.
-    fn dissect(&self, &[u8]) -> Result;
+    ///
+    /// `base` is the absolute offset of `data[0]` within the original
+    /// frame, so that a dissector nested inside another (e.g., `ip` inside
+    /// `ethernet`) can report byte ranges relative to the whole frame
+    /// rather than to its own local slice.
+    fn dissect(&self, data: &[u8], base: usize) -> Result;
 }
 
 
 /// A value parsed from a packet.
-///
-/// # TODO
-/// This value type isn't as expressive as would be required for a real
-/// Wireshark replacement just yet. Additional needs include:
-///
-///  * tracking original bytes (by reference or by index?)
-///
 #[derive(Debug)]
 pub enum Val {
     /// A signed integer, in machine-native representation.
@@ -100,7 +104,7 @@ pub enum Val {
     Unsigned { value: u64, radix: u8 },
 
     /// An integer value that represents a symbolic value.
-    Enum(u64, &'static str),
+    Enum(u64, Cow<'static, str>),
 
     /// A UTF-8–encoded string.
     String(String),
@@ -154,16 +158,35 @@ impl Val {
         Val::String(s.into())
     }
 
-    pub fn pretty_print(self, indent_level:usize) -> String {
+    /// Pretty-print this value, indenting subpackets by `indent_level`,
+    /// using the default `PrintOptions` plus `show_offsets` (kept as a
+    /// direct parameter since it's the option most callers care about).
+    ///
+    /// When `show_offsets` is set, every named field of a subpacket is
+    /// annotated with the byte range (`@offset+length`) it was parsed
+    /// from, e.g. `source port @0+2: 53`.
+    pub fn pretty_print(self, indent_level: usize, show_offsets: bool) -> String {
+        self.pretty_print_with(indent_level, &PrintOptions { show_offsets: show_offsets, ..PrintOptions::default() })
+    }
+
+    /// Pretty-print this value, indenting subpackets by `indent_level`,
+    /// rendering `Val::Bytes` blobs according to `options`.
+    pub fn pretty_print_with(self, indent_level: usize, options: &PrintOptions) -> String {
         match self {
             Val::Subpacket(values) => {
                 let indent:String = std::iter::repeat(" ").take(2 * indent_level).collect();
 
                 "\n".to_string() + &values.into_iter()
-                    .map(|(k,v)| {
-                        format!["{}{}: ", indent, k]
-                        + &match v {
-                            Ok(val) => val.pretty_print(indent_level + 1),
+                    .map(|field| {
+                        let offset = if options.show_offsets {
+                            format![" @{}+{}", field.range.start, field.range.end - field.range.start]
+                        } else {
+                            String::new()
+                        };
+
+                        format!["{}{}{}: ", indent, field.name, offset]
+                        + &match field.value {
+                            Ok(val) => val.pretty_print_with(indent_level + 1, options),
                             Err(e) => format!["<< Error: {} >>", e],
                         }
                     })
@@ -182,27 +205,103 @@ impl Val {
             Val::Enum(i, s) => format!["{} ({})", i, s],
             Val::String(ref s) => format!["{}", s],
             Val::Address { ref encoded, .. } => format!["{}", encoded],
-            Val::Bytes(ref bytes) => {
-                let mut s = format!["{} B [", bytes.len()];
+            Val::Bytes(ref bytes) => render_bytes(bytes, options),
+            Val::Warning(w) => format!["Warning: {}", w],
+            Val::Error(e) => format!["Error: {}", e],
+        }
+    }
+}
+
+/// How `pretty_print_with` should render a `Val::Bytes` blob.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BytesRendering {
+    /// The classic one-liner, `N B [ de ad be ef ... ]`, truncated to
+    /// `PrintOptions::truncate_at` bytes.
+    Inline,
+
+    /// A classic hex dump: 16 bytes per row, an offset column, the bytes
+    /// in hex, and an ASCII gutter with non-printable bytes shown as `.`.
+    HexDump,
+
+    /// Base64, for easy copy/paste into other tooling.
+    Base64,
+}
+
+/// Options controlling `Val::pretty_print_with`.
+#[derive(Clone, Copy, Debug)]
+pub struct PrintOptions {
+    /// Annotate each subpacket field with its `@offset+length` byte range.
+    pub show_offsets: bool,
+
+    /// How to render `Val::Bytes` blobs.
+    pub bytes: BytesRendering,
+
+    /// How many bytes of a blob to show before truncating. Only applies to
+    /// `BytesRendering::Inline`; `HexDump` and `Base64` always show
+    /// everything.
+    pub truncate_at: usize,
+}
+
+impl Default for PrintOptions {
+    fn default() -> PrintOptions {
+        PrintOptions {
+            show_offsets: false,
+            bytes: BytesRendering::Inline,
+            truncate_at: 16,
+        }
+    }
+}
+
+fn render_bytes(bytes: &[u8], options: &PrintOptions) -> String {
+    match options.bytes {
+        BytesRendering::Inline => {
+            let mut s = format!["{} B [", bytes.len()];
+
+            let to_print: &[u8] =
+                if bytes.len() <= options.truncate_at { bytes }
+                else { &bytes[..options.truncate_at] }
+                ;
+
+            for b in to_print {
+                s = s + &format![" {:02x}", b];
+            }
 
-                let to_print:&[u8] =
-                    if bytes.len() < 16 { bytes }
-                    else { &bytes[..16] }
-                    ;
+            if bytes.len() > options.truncate_at {
+                s = s + " ...";
+            }
 
-                for b in to_print {
-                    s = s + &format![" {:02x}", b];
+            s + " ]"
+        }
+
+        BytesRendering::HexDump => {
+            let mut s = format!["{} B", bytes.len()];
+
+            for (row, chunk) in bytes.chunks(16).enumerate() {
+                s = s + &format!["\n  {:08x}  ", row * 16];
+
+                for i in 0..16 {
+                    if i < chunk.len() {
+                        s = s + &format!["{:02x} ", chunk[i]];
+                    } else {
+                        s = s + "   ";
+                    }
+                    if i == 7 {
+                        s.push(' ');
+                    }
                 }
 
-                if bytes.len() > 16 {
-                    s = s + " ...";
+                s.push_str(" |");
+                for &b in chunk {
+                    let c = b as char;
+                    s.push(if c.is_ascii_graphic() || c == ' ' { c } else { '.' });
                 }
+                s.push('|');
+            }
 
-                s + " ]"
-            },
-            Val::Warning(w) => format!["Warning: {}", w],
-            Val::Error(e) => format!["Error: {}", e],
+            s
         }
+
+        BytesRendering::Base64 => format!["{} B, base64: {}", bytes.len(), base64::encode(bytes)],
     }
 }
 
@@ -244,8 +343,23 @@ impl fmt::Display for Error {
 pub type Result<T=Val> = ::std::result::Result<T,Error>;
 
 
-/// A named value-or-error.
-pub type NamedValue = (&'static str,Result<Val>);
+/// A named value-or-error, together with the byte range `[start, end)` in
+/// the original frame that it was parsed from (enabling Wireshark-style
+/// bidirectional highlighting between a field and its bytes).
+#[derive(Debug)]
+pub struct NamedValue {
+    pub name: Cow<'static, str>,
+    pub value: Result<Val>,
+    pub range: Range<usize>,
+}
+
+impl NamedValue {
+    pub fn new<S>(name: S, value: Result<Val>, range: Range<usize>) -> NamedValue
+        where S: Into<Cow<'static, str>>
+    {
+        NamedValue { name: name.into(), value: value, range: range }
+    }
+}
 
 
 /// Parse a signed integer of a given endianness from a byte buffer.
@@ -319,13 +433,18 @@ impl Protocol for RawBytes {
     fn short_name(&self) -> &'static str { self.short_name }
     fn full_name(&self) -> &'static str { self.full_name }
 
-    fn dissect(&self, data: &[u8]) -> Result {
+    fn dissect(&self, data: &[u8], base: usize) -> Result {
         Ok(Val::Subpacket(
-            vec![("raw data", Ok(Val::Bytes(data.to_vec())))]
+            vec![NamedValue::new("raw data", Ok(Val::Bytes(data.to_vec())), base..base + data.len())]
         ))
     }
 }
 
 
+pub mod capture;
+pub mod checksum;
 pub mod ethernet;
 pub mod ip;
+pub mod serialize;
+pub mod tftp;
+pub mod udp;
@@ -0,0 +1,720 @@
+/*
+ * Copyright 2015 Jonathan Anderson
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Structured, round-trippable serialization of a `Val` tree.
+//!
+//! `Val::pretty_print` is meant for humans and throws away information
+//! (e.g., it can't tell you whether an unsigned value was originally
+//! written in hex). `to_text()` and `to_binary()` instead produce a
+//! self-describing document that a GUI, test fixture, or diffing tool can
+//! parse back into an equivalent `Val` tree with `from_text()` /
+//! `from_binary()`. The two encodings describe exactly the same ordered
+//! tree and convert losslessly into one another.
+
+use byteorder::{BigEndian, ByteOrder};
+use std::borrow::Cow;
+
+use {Error, NamedValue, Val};
+
+/// A problem encountered while parsing a serialized `Val` tree.
+#[derive(Clone, Debug)]
+pub enum DecodeError {
+    /// The input ended before a complete value could be read.
+    Truncated,
+
+    /// The input contained a byte sequence that was not valid UTF-8.
+    InvalidUtf8,
+
+    /// The input contained a tag, token or structure that didn't make sense.
+    Malformed(String),
+}
+
+pub type DecodeResult<T> = Result<T, DecodeError>;
+
+impl Val {
+    /// Serialize this value tree to a compact, self-describing textual
+    /// encoding. See the module documentation for the round-trip guarantee.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        write_text_val(self, &mut out);
+        out
+    }
+
+    /// Serialize this value tree to a compact, self-describing binary
+    /// encoding. See the module documentation for the round-trip guarantee.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_binary_val(self, &mut out);
+        out
+    }
+
+    /// Parse a `Val` tree previously produced by `to_text()`.
+    pub fn from_text(s: &str) -> DecodeResult<Val> {
+        let mut reader = TextReader::new(s);
+        let val = try![reader.read_val()];
+        reader.skip_whitespace();
+        if reader.at_end() {
+            Ok(val)
+        } else {
+            Err(DecodeError::Malformed("trailing data after value".to_string()))
+        }
+    }
+
+    /// Parse a `Val` tree previously produced by `to_binary()`.
+    pub fn from_binary(data: &[u8]) -> DecodeResult<Val> {
+        let mut reader = BinaryReader::new(data);
+        let val = try![reader.read_val()];
+        if reader.at_end() {
+            Ok(val)
+        } else {
+            Err(DecodeError::Malformed("trailing data after value".to_string()))
+        }
+    }
+}
+
+//
+// Text encoding
+//
+// signed:     i<int>
+// unsigned:   u<radix>:<int>
+// enum:       e<int>:<quoted label>
+// string:     <quoted string>
+// address:    a<hex bytes>=<quoted encoded form>
+// bytes:      b[<hex> <hex> ...]
+// subpacket:  {<quoted name>@<start>-<end>:<result>, ...}
+// error:      !underflow(<expected>,<have>,<quoted subject>)
+//             !invalid(<quoted message>)
+// warning:    w<error, without the leading '!'>
+// result:     ok(<value>) | err(<error>)
+//
+
+fn write_text_val(val: &Val, out: &mut String) {
+    match *val {
+        Val::Signed(i) => out.push_str(&format!["i{}", i]),
+        Val::Unsigned { value, radix } => out.push_str(&format!["u{}:{}", radix, value]),
+        Val::Enum(n, ref label) => {
+            out.push_str(&format!["e{}:", n]);
+            write_text_string(label, out);
+        }
+        Val::String(ref s) => write_text_string(s, out),
+        Val::Address { ref bytes, ref encoded } => {
+            out.push('a');
+            write_text_bytes_hex(bytes, out);
+            out.push('=');
+            write_text_string(encoded, out);
+        }
+        Val::Bytes(ref bytes) => {
+            out.push_str("b[");
+            for (i, b) in bytes.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                out.push_str(&format!["{:02x}", b]);
+            }
+            out.push(']');
+        }
+        Val::Subpacket(ref fields) => {
+            out.push('{');
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_text_string(&field.name, out);
+                out.push_str(&format!["@{}-{}:", field.range.start, field.range.end]);
+                write_text_result(&field.value, out);
+            }
+            out.push('}');
+        }
+        Val::Error(ref e) => {
+            out.push('!');
+            write_text_error(e, out);
+        }
+        Val::Warning(ref e) => {
+            out.push('w');
+            write_text_error(e, out);
+        }
+    }
+}
+
+fn write_text_result(result: &Result<Val, Error>, out: &mut String) {
+    match *result {
+        Ok(ref val) => {
+            out.push_str("ok(");
+            write_text_val(val, out);
+            out.push(')');
+        }
+        Err(ref e) => {
+            out.push_str("err(");
+            write_text_error(e, out);
+            out.push(')');
+        }
+    }
+}
+
+fn write_text_error(e: &Error, out: &mut String) {
+    match *e {
+        Error::Underflow { expected, have, ref subject } => {
+            out.push_str(&format!["underflow({},{},", expected, have]);
+            write_text_string(subject, out);
+            out.push(')');
+        }
+        Error::InvalidData(ref msg) => {
+            out.push_str("invalid(");
+            write_text_string(msg, out);
+            out.push(')');
+        }
+    }
+}
+
+fn write_text_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_text_bytes_hex(bytes: &[u8], out: &mut String) {
+    for b in bytes {
+        out.push_str(&format!["{:02x}", b]);
+    }
+}
+
+/// A minimal hand-rolled recursive-descent reader for the text encoding.
+struct TextReader<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    _source: &'a str,
+}
+
+impl<'a> TextReader<'a> {
+    fn new(s: &'a str) -> TextReader<'a> {
+        TextReader { chars: s.chars().collect(), pos: 0, _source: s }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while !self.at_end() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> DecodeResult<char> {
+        self.chars.get(self.pos).cloned().ok_or(DecodeError::Truncated)
+    }
+
+    fn next(&mut self) -> DecodeResult<char> {
+        let c = try![self.peek()];
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn expect(&mut self, expected: char) -> DecodeResult<()> {
+        let c = try![self.next()];
+        if c == expected {
+            Ok(())
+        } else {
+            Err(DecodeError::Malformed(format!["expected '{}', found '{}'", expected, c]))
+        }
+    }
+
+    fn read_while<F: Fn(char) -> bool>(&mut self, pred: F) -> String {
+        let start = self.pos;
+        while !self.at_end() && pred(self.chars[self.pos]) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().cloned().collect()
+    }
+
+    fn read_int(&mut self) -> DecodeResult<i64> {
+        let negative = !self.at_end() && self.chars[self.pos] == '-';
+        if negative {
+            self.pos += 1;
+        }
+        let digits = self.read_while(|c| c.is_digit(10));
+        // Parse the sign together with the digits, so that `i64::MIN` (whose
+        // magnitude doesn't fit in an `i64`) still round-trips.
+        let s = if negative { format!["-{}", digits] } else { digits };
+        s.parse().map_err(|_| DecodeError::Malformed(format!["not an integer: '{}'", s]))
+    }
+
+    /// Like `read_int`, but for contexts (like a `start-end` byte range)
+    /// where a `-` means "next field", not "negative number".
+    fn read_uint(&mut self) -> DecodeResult<usize> {
+        let s = self.read_while(|c| c.is_digit(10));
+        s.parse().map_err(|_| DecodeError::Malformed(format!["not an integer: '{}'", s]))
+    }
+
+    /// Like `read_uint`, but parses directly into a `u64` rather than going
+    /// through `i64`, so values above `i64::MAX` (e.g. a 64-bit `Unsigned`
+    /// or `Enum` discriminant with the high bit set) round-trip correctly.
+    fn read_u64(&mut self) -> DecodeResult<u64> {
+        let s = self.read_while(|c| c.is_digit(10));
+        s.parse().map_err(|_| DecodeError::Malformed(format!["not an integer: '{}'", s]))
+    }
+
+    fn read_string(&mut self) -> DecodeResult<String> {
+        try![self.expect('"')];
+
+        let mut s = String::new();
+        loop {
+            let c = try![self.next()];
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = try![self.next()];
+                    s.push(match escaped {
+                        '"' => '"',
+                        '\\' => '\\',
+                        'n' => '\n',
+                        other => other,
+                    });
+                }
+                _ => s.push(c),
+            }
+        }
+    }
+
+    fn read_hex_bytes(&mut self) -> DecodeResult<Vec<u8>> {
+        let s = self.read_while(|c| c.is_digit(16));
+        if s.len() % 2 != 0 {
+            return Err(DecodeError::Malformed("odd number of hex digits".to_string()));
+        }
+
+        let mut bytes = Vec::with_capacity(s.len() / 2);
+        for i in 0..(s.len() / 2) {
+            let byte = try![u8::from_str_radix(&s[2 * i..2 * i + 2], 16)
+                .map_err(|_| DecodeError::Malformed(format!["invalid hex byte '{}'", &s[2 * i..2 * i + 2]]))];
+            bytes.push(byte);
+        }
+
+        Ok(bytes)
+    }
+
+    fn read_error(&mut self) -> DecodeResult<Error> {
+        let tag = self.read_while(|c| c.is_alphabetic());
+        match &tag[..] {
+            "underflow" => {
+                try![self.expect('(')];
+                let expected = try![self.read_int()] as usize;
+                try![self.expect(',')];
+                let have = try![self.read_int()] as usize;
+                try![self.expect(',')];
+                let subject = try![self.read_string()];
+                try![self.expect(')')];
+                Ok(Error::Underflow { expected: expected, have: have, subject: subject })
+            }
+            "invalid" => {
+                try![self.expect('(')];
+                let msg = try![self.read_string()];
+                try![self.expect(')')];
+                Ok(Error::InvalidData(msg))
+            }
+            other => Err(DecodeError::Malformed(format!["unknown error kind '{}'", other])),
+        }
+    }
+
+    fn read_result(&mut self) -> DecodeResult<Result<Val, Error>> {
+        let tag = self.read_while(|c| c.is_alphabetic());
+        try![self.expect('(')];
+
+        let result = match &tag[..] {
+            "ok" => Ok(try![self.read_val()]),
+            "err" => Err(try![self.read_error()]),
+            other => return Err(DecodeError::Malformed(format!["expected ok/err, found '{}'", other])),
+        };
+
+        try![self.expect(')')];
+        Ok(result)
+    }
+
+    fn read_val(&mut self) -> DecodeResult<Val> {
+        self.skip_whitespace();
+        let tag = try![self.next()];
+
+        match tag {
+            'i' => Ok(Val::Signed(try![self.read_int()])),
+
+            'u' => {
+                let radix = try![self.read_int()] as u8;
+                try![self.expect(':')];
+                let value = try![self.read_u64()];
+                Ok(Val::Unsigned { value: value, radix: radix })
+            }
+
+            'e' => {
+                let n = try![self.read_u64()];
+                try![self.expect(':')];
+                let label = try![self.read_string()];
+                Ok(Val::Enum(n, Cow::Owned(label)))
+            }
+
+            '"' => {
+                self.pos -= 1;
+                Ok(Val::String(try![self.read_string()]))
+            }
+
+            'a' => {
+                let bytes = try![self.read_hex_bytes()];
+                try![self.expect('=')];
+                let encoded = try![self.read_string()];
+                Ok(Val::Address { bytes: bytes, encoded: encoded })
+            }
+
+            'b' => {
+                try![self.expect('[')];
+                let mut bytes = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if try![self.peek()] == ']' {
+                        self.pos += 1;
+                        break;
+                    }
+                    let hi = try![self.next()];
+                    let lo = try![self.next()];
+                    let byte = try![u8::from_str_radix(&format!["{}{}", hi, lo], 16)
+                        .map_err(|_| DecodeError::Malformed("invalid hex byte".to_string()))];
+                    bytes.push(byte);
+                }
+                Ok(Val::Bytes(bytes))
+            }
+
+            '{' => {
+                let mut fields: Vec<NamedValue> = Vec::new();
+                self.skip_whitespace();
+                if try![self.peek()] == '}' {
+                    self.pos += 1;
+                    return Ok(Val::Subpacket(fields));
+                }
+
+                loop {
+                    self.skip_whitespace();
+                    let name = try![self.read_string()];
+                    try![self.expect('@')];
+                    let start = try![self.read_uint()];
+                    try![self.expect('-')];
+                    let end = try![self.read_uint()];
+                    self.skip_whitespace();
+                    try![self.expect(':')];
+                    let result = try![self.read_result()];
+                    fields.push(NamedValue::new(name, result, start..end));
+
+                    self.skip_whitespace();
+                    match try![self.next()] {
+                        ',' => continue,
+                        '}' => break,
+                        c => return Err(DecodeError::Malformed(format!["expected ',' or '}}', found '{}'", c])),
+                    }
+                }
+
+                Ok(Val::Subpacket(fields))
+            }
+
+            '!' => Ok(Val::Error(try![self.read_error()])),
+            'w' => Ok(Val::Warning(try![self.read_error()])),
+
+            other => Err(DecodeError::Malformed(format!["unknown value tag '{}'", other])),
+        }
+    }
+}
+
+//
+// Binary encoding
+//
+// A simple tag + length-prefixed-where-necessary TLV format, big-endian
+// throughout.
+//
+
+const TAG_SIGNED: u8 = 0;
+const TAG_UNSIGNED: u8 = 1;
+const TAG_ENUM: u8 = 2;
+const TAG_STRING: u8 = 3;
+const TAG_ADDRESS: u8 = 4;
+const TAG_BYTES: u8 = 5;
+const TAG_SUBPACKET: u8 = 6;
+const TAG_ERROR: u8 = 7;
+const TAG_WARNING: u8 = 8;
+
+const ERR_UNDERFLOW: u8 = 0;
+const ERR_INVALID_DATA: u8 = 1;
+
+const RESULT_OK: u8 = 0;
+const RESULT_ERR: u8 = 1;
+
+fn write_binary_val(val: &Val, out: &mut Vec<u8>) {
+    match *val {
+        Val::Signed(i) => {
+            out.push(TAG_SIGNED);
+            write_i64(i, out);
+        }
+        Val::Unsigned { value, radix } => {
+            out.push(TAG_UNSIGNED);
+            out.push(radix);
+            write_u64(value, out);
+        }
+        Val::Enum(n, ref label) => {
+            out.push(TAG_ENUM);
+            write_u64(n, out);
+            write_bytes(label.as_bytes(), out);
+        }
+        Val::String(ref s) => {
+            out.push(TAG_STRING);
+            write_bytes(s.as_bytes(), out);
+        }
+        Val::Address { ref bytes, ref encoded } => {
+            out.push(TAG_ADDRESS);
+            write_bytes(bytes, out);
+            write_bytes(encoded.as_bytes(), out);
+        }
+        Val::Bytes(ref bytes) => {
+            out.push(TAG_BYTES);
+            write_bytes(bytes, out);
+        }
+        Val::Subpacket(ref fields) => {
+            out.push(TAG_SUBPACKET);
+            write_u32(fields.len() as u32, out);
+            for field in fields {
+                write_bytes(field.name.as_bytes(), out);
+                write_u32(field.range.start as u32, out);
+                write_u32(field.range.end as u32, out);
+                write_binary_result(&field.value, out);
+            }
+        }
+        Val::Error(ref e) => {
+            out.push(TAG_ERROR);
+            write_binary_error(e, out);
+        }
+        Val::Warning(ref e) => {
+            out.push(TAG_WARNING);
+            write_binary_error(e, out);
+        }
+    }
+}
+
+fn write_binary_result(result: &Result<Val, Error>, out: &mut Vec<u8>) {
+    match *result {
+        Ok(ref val) => {
+            out.push(RESULT_OK);
+            write_binary_val(val, out);
+        }
+        Err(ref e) => {
+            out.push(RESULT_ERR);
+            write_binary_error(e, out);
+        }
+    }
+}
+
+fn write_binary_error(e: &Error, out: &mut Vec<u8>) {
+    match *e {
+        Error::Underflow { expected, have, ref subject } => {
+            out.push(ERR_UNDERFLOW);
+            write_u32(expected as u32, out);
+            write_u32(have as u32, out);
+            write_bytes(subject.as_bytes(), out);
+        }
+        Error::InvalidData(ref msg) => {
+            out.push(ERR_INVALID_DATA);
+            write_bytes(msg.as_bytes(), out);
+        }
+    }
+}
+
+fn write_u32(n: u32, out: &mut Vec<u8>) {
+    let mut buf = [0u8; 4];
+    BigEndian::write_u32(&mut buf, n);
+    out.extend_from_slice(&buf);
+}
+
+fn write_u64(n: u64, out: &mut Vec<u8>) {
+    let mut buf = [0u8; 8];
+    BigEndian::write_u64(&mut buf, n);
+    out.extend_from_slice(&buf);
+}
+
+fn write_i64(n: i64, out: &mut Vec<u8>) {
+    let mut buf = [0u8; 8];
+    BigEndian::write_i64(&mut buf, n);
+    out.extend_from_slice(&buf);
+}
+
+fn write_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    write_u32(bytes.len() as u32, out);
+    out.extend_from_slice(bytes);
+}
+
+/// A cursor over a binary-encoded `Val` document.
+struct BinaryReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BinaryReader<'a> {
+    fn new(data: &'a [u8]) -> BinaryReader<'a> {
+        BinaryReader { data: data, pos: 0 }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn take(&mut self, n: usize) -> DecodeResult<&'a [u8]> {
+        if self.pos + n > self.data.len() {
+            return Err(DecodeError::Truncated);
+        }
+        let slice = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn read_u8(&mut self) -> DecodeResult<u8> {
+        Ok(try![self.take(1)][0])
+    }
+
+    fn read_u32(&mut self) -> DecodeResult<u32> {
+        Ok(BigEndian::read_u32(try![self.take(4)]))
+    }
+
+    fn read_u64(&mut self) -> DecodeResult<u64> {
+        Ok(BigEndian::read_u64(try![self.take(8)]))
+    }
+
+    fn read_i64(&mut self) -> DecodeResult<i64> {
+        Ok(BigEndian::read_i64(try![self.take(8)]))
+    }
+
+    fn read_bytes(&mut self) -> DecodeResult<Vec<u8>> {
+        let len = try![self.read_u32()] as usize;
+        Ok(try![self.take(len)].to_vec())
+    }
+
+    fn read_string(&mut self) -> DecodeResult<String> {
+        let bytes = try![self.read_bytes()];
+        String::from_utf8(bytes).map_err(|_| DecodeError::InvalidUtf8)
+    }
+
+    fn read_error(&mut self) -> DecodeResult<Error> {
+        match try![self.read_u8()] {
+            ERR_UNDERFLOW => {
+                let expected = try![self.read_u32()] as usize;
+                let have = try![self.read_u32()] as usize;
+                let subject = try![self.read_string()];
+                Ok(Error::Underflow { expected: expected, have: have, subject: subject })
+            }
+            ERR_INVALID_DATA => Ok(Error::InvalidData(try![self.read_string()])),
+            other => Err(DecodeError::Malformed(format!["unknown error tag {}", other])),
+        }
+    }
+
+    fn read_result(&mut self) -> DecodeResult<Result<Val, Error>> {
+        match try![self.read_u8()] {
+            RESULT_OK => Ok(Ok(try![self.read_val()])),
+            RESULT_ERR => Ok(Err(try![self.read_error()])),
+            other => Err(DecodeError::Malformed(format!["unknown result tag {}", other])),
+        }
+    }
+
+    fn read_val(&mut self) -> DecodeResult<Val> {
+        match try![self.read_u8()] {
+            TAG_SIGNED => Ok(Val::Signed(try![self.read_i64()])),
+
+            TAG_UNSIGNED => {
+                let radix = try![self.read_u8()];
+                let value = try![self.read_u64()];
+                Ok(Val::Unsigned { value: value, radix: radix })
+            }
+
+            TAG_ENUM => {
+                let n = try![self.read_u64()];
+                let label = try![self.read_string()];
+                Ok(Val::Enum(n, Cow::Owned(label)))
+            }
+
+            TAG_STRING => Ok(Val::String(try![self.read_string()])),
+
+            TAG_ADDRESS => {
+                let bytes = try![self.read_bytes()];
+                let encoded = try![self.read_string()];
+                Ok(Val::Address { bytes: bytes, encoded: encoded })
+            }
+
+            TAG_BYTES => Ok(Val::Bytes(try![self.read_bytes()])),
+
+            TAG_SUBPACKET => {
+                let count = try![self.read_u32()] as usize;
+                // `count` comes straight from the input, so a truncated or
+                // malicious document could claim billions of fields; grow
+                // the `Vec` incrementally instead of trusting it enough to
+                // pre-allocate, so a bogus count costs nothing more than
+                // the `Truncated` error it immediately hits.
+                let mut fields = Vec::new();
+                for _ in 0..count {
+                    let name = try![self.read_string()];
+                    let start = try![self.read_u32()] as usize;
+                    let end = try![self.read_u32()] as usize;
+                    let result = try![self.read_result()];
+                    fields.push(NamedValue::new(name, result, start..end));
+                }
+                Ok(Val::Subpacket(fields))
+            }
+
+            TAG_ERROR => Ok(Val::Error(try![self.read_error()])),
+            TAG_WARNING => Ok(Val::Warning(try![self.read_error()])),
+
+            other => Err(DecodeError::Malformed(format!["unknown value tag {}", other])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tree that exercises every `Val` variant, including a value above
+    /// `i64::MAX` (see `TextReader::read_u64`), `i64::MIN` (see
+    /// `TextReader::read_int`) and a field whose value is itself an `Err`.
+    fn sample() -> Val {
+        Val::Subpacket(vec![
+            NamedValue::new("offset", Ok(Val::Signed(i64::min_value())), 0..0),
+            NamedValue::new("name", Ok(Val::str("a \"quoted\"\nname")), 0..0),
+            NamedValue::new("failure", Ok(Val::Error(Error::Underflow { expected: 2, have: 1, subject: "x".to_string() })), 0..0),
+            NamedValue::new("caution", Ok(Val::Warning(Error::InvalidData("odd".to_string()))), 0..0),
+            NamedValue::new("flag", Ok(Val::Unsigned { value: u64::max_value(), radix: 16 }), 0..8),
+            NamedValue::new("kind", Ok(Val::Enum(3, "DATA".into())), 8..10),
+            NamedValue::new(
+                "addr",
+                Ok(Val::Address { bytes: vec![192, 168, 0, 1], encoded: "192.168.0.1".to_string() }),
+                10..14,
+            ),
+            NamedValue::new("payload", Ok(Val::Bytes(vec![0xde, 0xad, 0xbe, 0xef])), 14..18),
+            NamedValue::new("note", Err(Error::InvalidData("bad".to_string())), 18..18),
+        ])
+    }
+
+    #[test]
+    fn text_round_trip_is_stable() {
+        let original = sample().to_text();
+        let reparsed = Val::from_text(&original).expect("a document we just produced should parse");
+        assert_eq!(reparsed.to_text(), original);
+    }
+
+    #[test]
+    fn binary_round_trip_is_stable() {
+        let original = sample().to_binary();
+        let reparsed = Val::from_binary(&original).expect("a document we just produced should parse");
+        assert_eq!(reparsed.to_binary(), original);
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * Copyright 2015 Jonathan Anderson
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Dissector for the Trivial File Transfer Protocol (TFTP), as carried
+//! over UDP (normally on port 69).
+
+use {Error, NamedValue, Result, Val};
+use byteorder::BigEndian;
+
+const OP_RRQ: u16 = 1;
+const OP_WRQ: u16 = 2;
+const OP_DATA: u16 = 3;
+const OP_ACK: u16 = 4;
+const OP_ERROR: u16 = 5;
+const OP_OACK: u16 = 6;
+
+/// Dissect a TFTP message. `base` is the offset of `data[0]` within the
+/// original frame.
+pub fn dissect(data: &[u8], base: usize) -> Result {
+    if data.len() < 2 {
+        return Error::underflow(2, data.len(), "TFTP opcode");
+    }
+
+    let opcode: u16 = try![::unsigned::<u16, BigEndian>(&data[0..2])];
+    let body = &data[2..];
+    let body_base = base + 2;
+
+    let label = match opcode {
+        OP_RRQ => "RRQ",
+        OP_WRQ => "WRQ",
+        OP_DATA => "DATA",
+        OP_ACK => "ACK",
+        OP_ERROR => "ERROR",
+        OP_OACK => "OACK",
+        _ => "unknown",
+    };
+
+    let fields = match opcode {
+        OP_RRQ | OP_WRQ => request(body, body_base),
+        OP_DATA => data_packet(body, body_base),
+        OP_ACK => ack(body, body_base),
+        OP_ERROR => error(body, body_base),
+        OP_OACK => options(body, body_base),
+        _ => Error::inval(format!["unknown TFTP opcode {}", opcode]),
+    };
+
+    fields.map(|mut f| {
+        f.insert(0, NamedValue::new("opcode", Ok(Val::Enum(opcode as u64, label.into())), base..base + 2));
+        Val::Subpacket(f)
+    })
+}
+
+/// A read or write request: a filename, a transfer mode and, optionally,
+/// a sequence of option/value pairs (RFC 2347).
+fn request(data: &[u8], base: usize) -> Result<Vec<NamedValue>> {
+    let (filename, consumed) = try![nul_terminated(data, "TFTP filename")];
+    let (mode, mode_consumed) = try![nul_terminated(&data[consumed..], "TFTP transfer mode")];
+    let after_mode = consumed + mode_consumed;
+
+    let mut fields = vec![
+        NamedValue::new("filename", Ok(Val::str(filename)), base..base + consumed),
+        NamedValue::new("mode", Ok(Val::str(mode)), base + consumed..base + after_mode),
+    ];
+
+    let rest = &data[after_mode..];
+    if !rest.is_empty() {
+        fields.push(try![options(rest, base + after_mode)].remove(0));
+    }
+
+    Ok(fields)
+}
+
+/// DATA: a 2 B block number followed by up to 512 B of payload.
+fn data_packet(data: &[u8], base: usize) -> Result<Vec<NamedValue>> {
+    if data.len() < 2 {
+        return Error::underflow(2, data.len(), "TFTP DATA block number");
+    }
+
+    let block: u16 = try![::unsigned::<u16, BigEndian>(&data[0..2])];
+    let payload = &data[2..];
+
+    if payload.len() > 512 {
+        return Error::inval(format!["TFTP DATA payload too large ({} B)", payload.len()]);
+    }
+
+    Ok(vec![
+        NamedValue::new("block", Val::base10(block), base..base + 2),
+        NamedValue::new("data", Ok(Val::Bytes(payload.to_vec())), base + 2..base + data.len()),
+    ])
+}
+
+/// ACK: just a 2 B block number.
+fn ack(data: &[u8], base: usize) -> Result<Vec<NamedValue>> {
+    if data.len() < 2 {
+        return Error::underflow(2, data.len(), "TFTP ACK block number");
+    }
+
+    let block: u16 = try![::unsigned::<u16, BigEndian>(&data[0..2])];
+    Ok(vec![NamedValue::new("block", Val::base10(block), base..base + 2)])
+}
+
+/// ERROR: a 2 B error code and a NUL-terminated human-readable message.
+fn error(data: &[u8], base: usize) -> Result<Vec<NamedValue>> {
+    if data.len() < 2 {
+        return Error::underflow(2, data.len(), "TFTP error code");
+    }
+
+    let code: u16 = try![::unsigned::<u16, BigEndian>(&data[0..2])];
+    let label = match code {
+        0 => "not defined",
+        1 => "file not found",
+        2 => "access violation",
+        3 => "disk full or allocation exceeded",
+        4 => "illegal TFTP operation",
+        5 => "unknown transfer ID",
+        6 => "file already exists",
+        7 => "no such user",
+        8 => "option negotiation failed",
+        _ => "unknown",
+    };
+
+    let (message, consumed) = try![nul_terminated(&data[2..], "TFTP error message")];
+
+    Ok(vec![
+        NamedValue::new("error code", Ok(Val::Enum(code as u64, label.into())), base..base + 2),
+        NamedValue::new("message", Ok(Val::str(message)), base + 2..base + 2 + consumed),
+    ])
+}
+
+/// OACK, or the trailing option/value pairs of a request: a run of
+/// NUL-terminated strings, alternating option name and option value
+/// (e.g., "blksize", "1428", "tsize", "0").
+fn options(data: &[u8], base: usize) -> Result<Vec<NamedValue>> {
+    let mut pairs = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let (name, name_consumed) = try![nul_terminated(&data[pos..], "TFTP option name")];
+        let (value, value_consumed) = try![nul_terminated(&data[pos + name_consumed..], "TFTP option value")];
+
+        let key = match &name.to_lowercase()[..] {
+            "blksize" => "blksize",
+            "tsize" => "tsize",
+            "timeout" => "timeout",
+            _ => "option",
+        };
+
+        let end = pos + name_consumed + value_consumed;
+        pairs.push(NamedValue::new(key, Ok(Val::str(value)), base + pos..base + end));
+        pos = end;
+    }
+
+    Ok(vec![NamedValue::new("options", Ok(Val::Subpacket(pairs)), base..base + data.len())])
+}
+
+/// Read a NUL-terminated string from the front of `data`, returning the
+/// decoded string and the number of bytes consumed (including the
+/// terminating NUL).
+fn nul_terminated(data: &[u8], subject: &str) -> Result<(String, usize)> {
+    match data.iter().position(|&b| b == 0) {
+        Some(i) => Ok((String::from_utf8_lossy(&data[..i]).into_owned(), i + 1)),
+        None => Error::underflow(data.len() + 1, data.len(), subject),
+    }
+}
@@ -0,0 +1,88 @@
+/*
+ * Copyright 2015 Jonathan Anderson
+ *
+ * Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+ * http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+ * http://opensource.org/licenses/MIT>, at your option. This file may not be
+ * copied, modified, or distributed except according to those terms.
+ */
+
+//! Dissector for the User Datagram Protocol (UDP).
+//!
+//! UDP itself is a thin header in front of whatever application-layer
+//! protocol is actually being carried; `dissect()` parses that header and
+//! then dispatches to an inner dissector based on well-known port numbers.
+
+use {Error, NamedValue, Result, Val};
+use byteorder::BigEndian;
+
+use checksum;
+use tftp;
+
+/// The well-known port used by the Trivial File Transfer Protocol.
+const TFTP_PORT: u16 = 69;
+
+/// Dissect a UDP datagram. `base` is the offset of `data[0]` within the
+/// original frame.
+///
+/// UDP's checksum is computed over an IP pseudo-header (source/destination
+/// address and protocol) in addition to the UDP header and payload, so
+/// verifying it requires that pseudo-header from whichever dissector
+/// (`ip`, `ipv6`, ...) called us. Pass it as `pseudo_header` when available;
+/// pass `None` to skip verification rather than reporting a false positive.
+pub fn dissect(data: &[u8], base: usize, pseudo_header: Option<&[u8]>) -> Result {
+    if data.len() < 8 {
+        return Error::underflow(8, data.len(), "UDP header");
+    }
+
+    let source: u16 = try![::unsigned::<u16, BigEndian>(&data[0..2])];
+    let dest: u16 = try![::unsigned::<u16, BigEndian>(&data[2..4])];
+    let length: u16 = try![::unsigned::<u16, BigEndian>(&data[4..6])];
+    let checksum: u16 = try![::unsigned::<u16, BigEndian>(&data[6..8])];
+
+    // `length` covers the UDP header and payload; anything past it is
+    // link-layer padding, not part of this datagram. Trim to whichever of
+    // `length` or the actual buffer is shorter, so padding doesn't leak
+    // into the inner dissector (or, below, into the checksum).
+    let declared_len = (length as usize).saturating_sub(8);
+    let payload_len = ::std::cmp::min(declared_len, data.len() - 8);
+    let datagram = &data[..8 + payload_len];
+    let payload = &data[8..8 + payload_len];
+    let payload_base = base + 8;
+
+    let body = if source == TFTP_PORT || dest == TFTP_PORT {
+        NamedValue::new("TFTP", tftp::dissect(payload, payload_base), payload_base..payload_base + payload_len)
+    } else {
+        NamedValue::new("data", Ok(Val::Bytes(payload.to_vec())), payload_base..payload_base + payload_len)
+    };
+
+    let mut fields = vec![
+        NamedValue::new("source port", Val::base10(source), base..base + 2),
+        NamedValue::new("destination port", Val::base10(dest), base + 2..base + 4),
+        NamedValue::new("length", Val::base10(length), base + 4..base + 6),
+        NamedValue::new("checksum", Val::base16(checksum), base + 6..base + 8),
+        body,
+    ];
+
+    // A checksum of zero means the sender chose not to checksum this
+    // datagram (RFC 768); anything else should sum to zero once the
+    // pseudo-header and checksum field itself are included. Without the
+    // pseudo-header we can't compute a meaningful checksum at all, so we
+    // stay silent rather than flag every correctly-checksummed datagram.
+    if let Some(pseudo_header) = pseudo_header {
+        if checksum != 0 {
+            let mut verified = pseudo_header.to_vec();
+            verified.extend_from_slice(datagram);
+
+            if checksum::internet(&verified) != 0 {
+                fields.push(NamedValue::new(
+                    "checksum warning",
+                    Ok(Val::Warning(Error::InvalidData(format!["bad UDP checksum ({:#06x})", checksum]))),
+                    base + 6..base + 8,
+                ));
+            }
+        }
+    }
+
+    Ok(Val::Subpacket(fields))
+}